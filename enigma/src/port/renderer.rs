@@ -1,6 +1,6 @@
-// use crate::keymap::{At, CharSearch, Movement, usize, Word};
+// use crate::keymap::{At, Movement, usize};
 use std::fmt;
-use std::io::Write;
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::iter;
 use std::ops::{Deref, Index, Range};
 use std::string::Drain;
@@ -23,12 +23,129 @@ impl Default for Direction {
     }
 }
 
+/// Word boundary definition used by word-wise movement and editing.
+///
+/// `Emacs` and `Vi` agree on what a word *is* (a Unicode word segment that
+/// starts with an alphanumeric character, so `foo,bar` is two words while
+/// `foo.bar` and `can't` are one), but disagree on where forward motion lands: `move_to_next_word`
+/// with `Emacs` stops at the end of the current/next word (`M-f`), while `Vi`
+/// stops at the start of the following word (`w`). Backward motion (`b`) is
+/// the same for both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Word {
+    /// Emacs-style: a word is a Unicode word segment starting with an
+    /// alphanumeric character; forward motion lands on the end of a word.
+    Emacs,
+    /// vi's "WORD" (`W`/`B`/`E`): a word is any run of non-blank characters.
+    Big,
+    /// vi's "word" (`w`/`b`/`e`): like `Emacs`'s word definition, but
+    /// forward motion lands on the start of the following word.
+    Vi,
+}
+
+/// Case operation applied to a word by [`LineBuffer::edit_word`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WordAction {
+    /// Uppercase the first grapheme, lowercase the rest.
+    Capitalize,
+    Uppercase,
+    Lowercase,
+}
+
+/// An in-line character search, as used by vi's `f`/`F`/`t`/`T`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CharSearch {
+    /// `f`: find `char` moving right, landing on it.
+    Forward(char),
+    /// `F`: find `char` moving left, landing on it.
+    Backward(char),
+    /// `t`: find `char` moving right, landing one grapheme before it.
+    ForwardBefore(char),
+    /// `T`: find `char` moving left, landing one grapheme after it.
+    BackwardAfter(char),
+}
+
+/// Maximum number of entries retained by a `KillRing`.
+const KILL_RING_SIZE: usize = 10;
+
+/// A bounded ring buffer of recently killed text, providing emacs-style
+/// yank/yank-pop.
+#[derive(Debug)]
+struct KillRing {
+    slots: std::collections::VecDeque<String>,
+    /// Index of the slot last handed back by `yank`/`yank_pop`.
+    index: usize,
+}
+
+impl KillRing {
+    fn new() -> Self {
+        Self {
+            slots: std::collections::VecDeque::with_capacity(KILL_RING_SIZE),
+            index: 0,
+        }
+    }
+
+    /// Records freshly killed `text`. When `chain` is set (a consecutive
+    /// kill in the same direction as the last one), `text` is concatenated
+    /// onto the most recent entry instead of starting a new one.
+    fn kill(&mut self, text: &str, dir: Direction, chain: bool) {
+        if text.is_empty() {
+            return;
+        }
+        if chain {
+            if let Some(last) = self.slots.back_mut() {
+                match dir {
+                    Direction::Forward => last.push_str(text),
+                    Direction::Backward => last.insert_str(0, text),
+                }
+                self.index = self.slots.len() - 1;
+                return;
+            }
+        }
+        if self.slots.len() == KILL_RING_SIZE {
+            self.slots.pop_front();
+        }
+        self.slots.push_back(text.to_owned());
+        self.index = self.slots.len() - 1;
+    }
+
+    /// Returns the most recently killed text, if any.
+    fn yank(&mut self) -> Option<&str> {
+        if self.slots.is_empty() {
+            return None;
+        }
+        self.index = self.slots.len() - 1;
+        self.slots.back().map(String::as_str)
+    }
+
+    /// Returns the entry preceding the last one handed back, cycling back
+    /// to the most recent when the ring is exhausted.
+    fn yank_pop(&mut self) -> Option<&str> {
+        if self.slots.is_empty() {
+            return None;
+        }
+        self.index = if self.index == 0 {
+            self.slots.len() - 1
+        } else {
+            self.index - 1
+        };
+        self.slots.get(self.index).map(String::as_str)
+    }
+}
+
 /// Represents the current input (text and cursor position).
 ///
 /// The methods do text manipulations or/and cursor movements.
 pub struct LineBuffer {
     buf: String, // Edited line buffer (rl_line_buffer)
     pos: usize,  // Current cursor position (byte position) (rl_point)
+    kill_ring: KillRing,
+    // direction of the last kill, used to chain consecutive kills together
+    last_kill_dir: Option<Direction>,
+    // byte range of the text inserted by the last yank/yank-pop
+    last_yank: Option<Range<usize>>,
+    // last f/F/t/T search, for `;`/`,` repeats
+    last_char_search: Option<CharSearch>,
 }
 
 impl fmt::Debug for LineBuffer {
@@ -46,6 +163,10 @@ impl LineBuffer {
         Self {
             buf: String::with_capacity(capacity),
             pos: 0,
+            kill_ring: KillRing::new(),
+            last_kill_dir: None,
+            last_yank: None,
+            last_char_search: None,
         }
     }
 
@@ -88,9 +209,17 @@ impl LineBuffer {
         self.buf.is_empty()
     }
 
+    /// Clears kill-chaining and yank tracking state, called whenever an
+    /// edit happens that isn't itself a kill or a yank/yank-pop.
+    fn reset_edit_state(&mut self) {
+        self.last_kill_dir = None;
+        self.last_yank = None;
+    }
+
     /// Set line content (`buf`) and cursor position (`pos`).
     pub fn update(&mut self, buf: &str, pos: usize) {
         assert!(pos <= buf.len());
+        self.reset_edit_state();
         let end = self.len();
         self.drain(0..end, Direction::default());
         let max = self.buf.capacity();
@@ -109,6 +238,7 @@ impl LineBuffer {
 
     /// Clear the buffer
     pub fn clear(&mut self) {
+        self.reset_edit_state();
         let end = self.len();
         self.drain(0..end, Direction::default());
         self.pos = 0;
@@ -146,6 +276,7 @@ impl LineBuffer {
     /// Return `None` when maximum buffer size has been reached,
     /// `true` when the character has been appended to the end of the line.
     pub fn insert(&mut self, ch: char, n: usize) -> Option<bool> {
+        self.reset_edit_state();
         let shift = ch.len_utf8() * n;
         // if self.buf.len() + shift > self.buf.capacity() {
         //     return None;
@@ -204,6 +335,272 @@ impl LineBuffer {
         }
     }
 
+    /// Moves the cursor forward `n` words according to `word_def`: to the
+    /// end of the `n`-th word for `Word::Emacs`, to the start of the `n`-th
+    /// following word for `Word::Big`/`Word::Vi`. Returns `false` if the cursor doesn't move
+    /// (already at the end of the buffer).
+    pub(crate) fn move_to_next_word(&mut self, word_def: Word, n: usize) -> bool {
+        match self.next_word_pos(word_def, n) {
+            Some(pos) if pos != self.pos => {
+                self.pos = pos;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Moves the cursor to the start of the `n`-th word preceding it,
+    /// according to `word_def`. Returns `false` if the cursor doesn't move
+    /// (already at the start of the buffer).
+    pub(crate) fn move_to_prev_word(&mut self, word_def: Word, n: usize) -> bool {
+        match self.prev_word_pos(word_def, n) {
+            Some(pos) if pos != self.pos => {
+                self.pos = pos;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Deletes the `n`-th word following (`Direction::Forward`) or
+    /// preceding (`Direction::Backward`) the cursor, according to
+    /// `word_def`, and returns the removed text.
+    pub(crate) fn delete_word(
+        &mut self,
+        dir: Direction,
+        word_def: Word,
+        n: usize,
+    ) -> Option<String> {
+        let range = match dir {
+            Direction::Forward => self.pos..self.next_word_pos(word_def, n)?,
+            Direction::Backward => self.prev_word_pos(word_def, n)?..self.pos,
+        };
+        let start = range.start;
+        let chars = self.kill(range, dir);
+        self.pos = start;
+        Some(chars)
+    }
+
+    /// Applies `action` to the word at (or following) the cursor, then
+    /// moves the cursor just past the edited word.
+    pub(crate) fn edit_word(&mut self, action: WordAction, word_def: Word) -> bool {
+        let starts = self.word_start_positions(word_def);
+        let start = match starts
+            .iter()
+            .rev()
+            .find(|&&i| i <= self.pos && self.word_end_pos(i, word_def) > self.pos)
+        {
+            Some(&i) => i,
+            None => match starts.iter().find(|&&i| i >= self.pos) {
+                Some(&i) => i,
+                None => return false,
+            },
+        };
+        let end = self.word_end_pos(start, word_def);
+        if start == end {
+            return false;
+        }
+        let word = &self.buf[start..end];
+        let edited = match action {
+            WordAction::Uppercase => word.to_uppercase(),
+            WordAction::Lowercase => word.to_lowercase(),
+            WordAction::Capitalize => {
+                let mut graphemes = word.graphemes(true);
+                let mut out = String::with_capacity(word.len());
+                if let Some(first) = graphemes.next() {
+                    out.push_str(&first.to_uppercase());
+                }
+                out.push_str(&graphemes.as_str().to_lowercase());
+                out
+            }
+        };
+        self.replace(start..end, &edited);
+        true
+    }
+
+    /// Moves the cursor to the `n`-th match of `search`. Returns `false`
+    /// (and leaves the cursor untouched) if fewer than `n` matches exist.
+    pub(crate) fn move_to_char(&mut self, search: CharSearch, n: usize) -> bool {
+        self.last_char_search = Some(search);
+        match self.search_char(search, n) {
+            Some(pos) => {
+                self.pos = pos;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Repeats the last `move_to_char` search. `reverse` flips its
+    /// direction, mirroring vi's `,` (the reverse of `;`). Returns `false`
+    /// if there was no previous search or fewer than `n` matches exist.
+    pub fn repeat_last_char_search(&mut self, reverse: bool, n: usize) -> bool {
+        let search = match self.last_char_search {
+            Some(search) if reverse => Self::reverse_char_search(search),
+            Some(search) => search,
+            None => return false,
+        };
+        match self.search_char(search, n) {
+            Some(pos) => {
+                self.pos = pos;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn reverse_char_search(search: CharSearch) -> CharSearch {
+        match search {
+            CharSearch::Forward(c) => CharSearch::Backward(c),
+            CharSearch::Backward(c) => CharSearch::Forward(c),
+            CharSearch::ForwardBefore(c) => CharSearch::BackwardAfter(c),
+            CharSearch::BackwardAfter(c) => CharSearch::ForwardBefore(c),
+        }
+    }
+
+    /// Returns the byte position of the `n`-th match of `search`, scanning
+    /// grapheme by grapheme from the cursor. Returns `None` if fewer than
+    /// `n` matches exist.
+    fn search_char(&self, search: CharSearch, n: usize) -> Option<usize> {
+        debug_assert!(n > 0);
+        match search {
+            CharSearch::Forward(c) => self.buf[self.pos..]
+                .grapheme_indices(true)
+                .skip(1) // don't match the grapheme under the cursor
+                .filter(|&(_, g)| g.starts_with(c))
+                .nth(n - 1)
+                .map(|(i, _)| self.pos + i),
+            CharSearch::ForwardBefore(c) => self.buf[self.pos..]
+                .grapheme_indices(true)
+                .skip(1)
+                .filter(|&(_, g)| g.starts_with(c))
+                .nth(n - 1)
+                .and_then(|(i, _)| self.grapheme_before(self.pos + i)),
+            CharSearch::Backward(c) => self.buf[..self.pos]
+                .grapheme_indices(true)
+                .rev()
+                .filter(|&(_, g)| g.starts_with(c))
+                .nth(n - 1)
+                .map(|(i, _)| i),
+            CharSearch::BackwardAfter(c) => self.buf[..self.pos]
+                .grapheme_indices(true)
+                .rev()
+                .filter(|&(_, g)| g.starts_with(c))
+                .nth(n - 1)
+                .and_then(|(i, _)| self.grapheme_after(i)),
+        }
+    }
+
+    /// Byte position of the grapheme immediately preceding `idx`.
+    fn grapheme_before(&self, idx: usize) -> Option<usize> {
+        self.buf[..idx]
+            .grapheme_indices(true)
+            .next_back()
+            .map(|(i, _)| i)
+    }
+
+    /// Byte position one grapheme after `idx`.
+    fn grapheme_after(&self, idx: usize) -> Option<usize> {
+        self.buf[idx..]
+            .grapheme_indices(true)
+            .nth(1)
+            .map(|(i, _)| idx + i)
+    }
+
+    /// Returns `true` if `fragment` (a boundary yielded by
+    /// `split_word_bound_indices`) is the start of an actual word rather
+    /// than a run of whitespace, according to `word_def`. `prev_is_boundary`
+    /// is whether the preceding fragment was itself whitespace (or there is
+    /// none), which is what lets `Word::Big` treat punctuation glued onto a
+    /// word as part of that word instead of a split point.
+    fn is_word_start(word_def: Word, prev_is_boundary: bool, fragment: &str) -> bool {
+        match fragment.chars().next() {
+            None => false,
+            Some(c) if c.is_whitespace() => false,
+            Some(c) => match word_def {
+                Word::Big => prev_is_boundary,
+                Word::Emacs | Word::Vi => c.is_alphanumeric(),
+            },
+        }
+    }
+
+    /// Byte positions of every word start in the buffer, according to
+    /// `word_def`.
+    fn word_start_positions(&self, word_def: Word) -> Vec<usize> {
+        let mut starts = Vec::new();
+        let mut prev_is_boundary = true;
+        for (i, frag) in self.buf.split_word_bound_indices() {
+            if Self::is_word_start(word_def, prev_is_boundary, frag) {
+                starts.push(i);
+            }
+            prev_is_boundary = frag.chars().next().is_none_or(char::is_whitespace);
+        }
+        starts
+    }
+
+    /// Byte position where the word starting at `start` ends.
+    fn word_end_pos(&self, start: usize, word_def: Word) -> usize {
+        match word_def {
+            Word::Big => self.buf[start..]
+                .find(char::is_whitespace)
+                .map_or(self.buf.len(), |i| start + i),
+            Word::Emacs | Word::Vi => self.buf[start..]
+                .split_word_bound_indices()
+                .next()
+                .map_or(self.buf.len(), |(_, frag)| start + frag.len()),
+        }
+    }
+
+    /// Byte positions of every word end in the buffer, according to
+    /// `word_def`. Used by `Word::Emacs` forward motion, which lands on the
+    /// end of a word rather than the start of the following one.
+    fn word_end_positions(&self, word_def: Word) -> Vec<usize> {
+        let mut prev_is_boundary = true;
+        let mut ends = Vec::new();
+        for (i, frag) in self.buf.split_word_bound_indices() {
+            if Self::is_word_start(word_def, prev_is_boundary, frag) {
+                ends.push(self.word_end_pos(i, word_def));
+            }
+            prev_is_boundary = frag.chars().next().is_none_or(char::is_whitespace);
+        }
+        ends
+    }
+
+    /// Byte position of the `n`-th word boundary after the cursor, or the
+    /// end of the buffer if fewer than `n` remain. For `Word::Emacs` this is
+    /// the end of the `n`-th word (`M-f`); for `Word::Big`/`Word::Vi` it's
+    /// the start of the `n`-th following word (`w`).
+    fn next_word_pos(&self, word_def: Word, n: usize) -> Option<usize> {
+        if self.pos == self.buf.len() {
+            return None;
+        }
+        let positions = match word_def {
+            Word::Emacs => self.word_end_positions(word_def),
+            Word::Big | Word::Vi => self.word_start_positions(word_def),
+        };
+        let mut it = positions.into_iter().filter(|&i| i > self.pos);
+        let mut last = None;
+        for _ in 0..n {
+            last = Some(it.next().unwrap_or(self.buf.len()));
+        }
+        last
+    }
+
+    /// Byte position of the start of the `n`-th word before the cursor, or
+    /// the start of the buffer if fewer than `n` words precede it.
+    fn prev_word_pos(&self, word_def: Word, n: usize) -> Option<usize> {
+        if self.pos == 0 {
+            return None;
+        }
+        let starts = self.word_start_positions(word_def);
+        let mut it = starts.into_iter().rev().filter(|&i| i < self.pos);
+        let mut last = None;
+        for _ in 0..n {
+            last = Some(it.next().unwrap_or(0));
+        }
+        last
+    }
+
     /// Delete the character at the right of the cursor without altering the
     /// cursor position. Basically this is what happens with the "Delete"
     /// keyboard key.
@@ -212,10 +609,7 @@ impl LineBuffer {
         match self.next_pos(n) {
             Some(pos) => {
                 let start = self.pos;
-                let chars = self
-                    .drain(start..pos, Direction::Forward)
-                    .collect::<String>();
-                Some(chars)
+                Some(self.kill(start..pos, Direction::Forward))
             }
             None => None,
         }
@@ -227,7 +621,7 @@ impl LineBuffer {
         match self.prev_pos(n) {
             Some(pos) => {
                 let end = self.pos;
-                self.drain(pos..end, Direction::Backward);
+                self.kill(pos..end, Direction::Backward);
                 self.pos = pos;
                 true
             }
@@ -240,7 +634,7 @@ impl LineBuffer {
         if !self.buf.is_empty() && self.pos < self.buf.len() {
             let start = self.pos;
             let end = self.buf.len();
-            self.drain(start..end, Direction::Forward);
+            self.kill(start..end, Direction::Forward);
             true
         } else {
             false
@@ -251,7 +645,7 @@ impl LineBuffer {
     pub fn discard_line(&mut self) -> bool {
         if self.pos > 0 && !self.buf.is_empty() {
             let end = self.pos;
-            self.drain(0..end, Direction::Backward);
+            self.kill(0..end, Direction::Backward);
             self.pos = 0;
             true
         } else {
@@ -259,9 +653,62 @@ impl LineBuffer {
         }
     }
 
+    /// Removes `range` from the buffer and records the removed text into
+    /// the kill ring, chaining onto the previous entry when this kill is
+    /// consecutive with (and in the same `dir` as) the last one. The single
+    /// internal entry point for the killing operations (`delete`,
+    /// `backspace`, `kill_line`, `discard_line`, `delete_word`); `clear`,
+    /// `update`, `replace` and `delete_range` remove text without recording
+    /// it.
+    fn kill(&mut self, range: Range<usize>, dir: Direction) -> String {
+        let chain = self.last_kill_dir == Some(dir);
+        let killed = self.drain(range, dir).collect::<String>();
+        self.kill_ring.kill(&killed, dir, chain);
+        self.last_kill_dir = Some(dir);
+        self.last_yank = None;
+        killed
+    }
+
+    /// Inserts the most recently killed text at the cursor, `n` times, and
+    /// remembers the inserted range so a following `yank_pop` can replace
+    /// it. Returns `false` if the kill ring is empty.
+    pub fn yank(&mut self, n: usize) -> bool {
+        let text = match self.kill_ring.yank() {
+            Some(text) => text.to_owned(),
+            None => return false,
+        };
+        let start = self.pos;
+        for _ in 0..n {
+            self.insert_str(self.pos, &text);
+            self.pos += text.len();
+        }
+        self.last_kill_dir = None;
+        self.last_yank = Some(start..self.pos);
+        true
+    }
+
+    /// Replaces the text inserted by the last `yank`/`yank_pop` with the
+    /// previous kill-ring entry. Returns `false` if there was no preceding
+    /// yank to pop.
+    pub fn yank_pop(&mut self) -> bool {
+        let range = match self.last_yank.clone() {
+            Some(range) => range,
+            None => return false,
+        };
+        let text = match self.kill_ring.yank_pop() {
+            Some(text) => text.to_owned(),
+            None => return false,
+        };
+        let start = range.start;
+        self.replace(range, &text);
+        self.last_yank = Some(start..self.pos);
+        true
+    }
+
     /// Replaces the content between [`start`..`end`] with `text`
     /// and positions the cursor to the end of text.
     pub fn replace(&mut self, range: Range<usize>, text: &str) {
+        self.reset_edit_state();
         let start = range.start;
         self.buf.drain(range);
         if start == self.buf.len() {
@@ -303,6 +750,71 @@ impl Deref for LineBuffer {
     }
 }
 
+impl Seek for LineBuffer {
+    /// Reinterprets `SeekFrom` offsets as grapheme counts rather than raw
+    /// byte offsets (reusing `move_forward`/`move_backward`, which already
+    /// walk `next_pos`/`prev_pos`): `Start(n)` is `n` graphemes from the
+    /// start, `End(n)` is `n` graphemes from the end (negative moves
+    /// further back), and `Current(n)` is `n` graphemes from the cursor
+    /// (negative moves left). Always lands on a grapheme boundary and
+    /// clamps to `[0, len]` rather than erroring.
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match pos {
+            SeekFrom::Start(n) => {
+                self.move_home();
+                self.move_forward(n as usize);
+            }
+            SeekFrom::End(n) => {
+                self.move_end();
+                if n >= 0 {
+                    self.move_forward(n as usize);
+                } else {
+                    self.move_backward((-n) as usize);
+                }
+            }
+            SeekFrom::Current(n) => {
+                if n >= 0 {
+                    self.move_forward(n as usize);
+                } else {
+                    self.move_backward((-n) as usize);
+                }
+            }
+        }
+        Ok(self.pos as u64)
+    }
+}
+
+impl Write for LineBuffer {
+    /// Inserts `buf` (which must be valid UTF-8) at the cursor.
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let s = std::str::from_utf8(buf)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        self.reset_edit_state();
+        self.insert_str(self.pos, s);
+        self.pos += s.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for LineBuffer {
+    /// Reads raw bytes from the cursor onward, snapping the amount read
+    /// down so the cursor never lands on a non-char-boundary.
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        let bytes = &self.buf.as_bytes()[self.pos..];
+        let mut n = bytes.len().min(out.len());
+        while n > 0 && !self.buf.is_char_boundary(self.pos + n) {
+            n -= 1;
+        }
+        out[..n].copy_from_slice(&bytes[..n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct Position {
     pub col: usize,
@@ -312,6 +824,10 @@ pub struct Position {
 pub(super) struct Renderer {
     line: LineBuffer,
     out: std::io::Stdout,
+    /// Row (counted from the top of the rendered region) that the last
+    /// refresh left the cursor on, so the cursor can be walked back up to
+    /// the top of the rendered region before redrawing.
+    rows: usize,
 }
 
 impl Renderer {
@@ -319,7 +835,52 @@ impl Renderer {
         Self {
             line: LineBuffer::with_capacity(512),
             out,
+            rows: 0,
+        }
+    }
+
+    /// Repaints the whole buffer, wrapping at `COLS` and honoring embedded
+    /// newlines, and leaves the cursor at its logical position.
+    ///
+    /// `self.rows` tracks the row the cursor was left on (not the row count
+    /// of the whole buffer): the next `refresh` walks the physical cursor
+    /// back up by exactly that many rows before clearing, so it must match
+    /// where the cursor actually ended up, or the walk-up overshoots and
+    /// `clear::AfterCursor` clobbers content above the input region.
+    fn refresh(&mut self) {
+        if self.rows > 0 {
+            write!(self.out, "{}", termion::cursor::Up(self.rows as u16)).unwrap();
+        }
+        write!(self.out, "\r{}", termion::clear::AfterCursor).unwrap();
+
+        let buf = self.line.as_str();
+        let mut parts = buf.split('\n');
+        if let Some(first) = parts.next() {
+            self.out.write_all(first.as_bytes()).unwrap();
+        }
+        for part in parts {
+            self.out.write_all(b"\r\n").unwrap();
+            self.out.write_all(part.as_bytes()).unwrap();
+        }
+
+        let end = calculate_position(buf, Position::default());
+        let cursor = calculate_position(&buf[..self.line.pos()], Position::default());
+
+        if end.row > cursor.row {
+            write!(
+                self.out,
+                "{}",
+                termion::cursor::Up((end.row - cursor.row) as u16)
+            )
+            .unwrap();
         }
+        write!(self.out, "\r").unwrap();
+        if cursor.col > 0 {
+            write!(self.out, "{}", termion::cursor::Right(cursor.col as u16)).unwrap();
+        }
+
+        self.rows = cursor.row;
+        self.out.flush().unwrap();
     }
 
     pub fn beep(&mut self) {
@@ -331,11 +892,10 @@ impl Renderer {
         use std::convert::TryInto;
         let pos = i16::from_be_bytes(bytes[0..2].try_into().unwrap());
         // info!("move: pos={}", pos);
+        self.line.seek(SeekFrom::Current(pos as i64)).unwrap();
         if pos < 0 {
-            self.line.move_backward(-pos as usize);
             write!(self.out, "{}", termion::cursor::Left(-pos as u16));
         } else {
-            self.line.move_forward(pos as usize);
             write!(self.out, "{}", termion::cursor::Right(pos as u16));
         }
         self.out.flush().unwrap();
@@ -359,25 +919,13 @@ impl Renderer {
     }
 
     pub fn insert_chars(&mut self, chars: &[u8]) {
-        for c in chars {
-            if *c == b'\n' {
-                self.out.write_all(b"\r\n").unwrap();
-                self.out.flush().unwrap();
-                self.line.clear();
-            } else {
-                assert!(self.line.insert(*c as char, 1).is_some())
-            }
-            // move cursor
+        // `chars` comes straight off the wire: a malformed frame must beep,
+        // not crash the port.
+        if self.line.write_all(chars).is_err() {
+            self.beep();
+            return;
         }
-        // TODO: need to redraw more efficiently and with multiline
-        write!(self.out, "\r{}", termion::clear::CurrentLine);
-        self.out.write_all(self.line.as_str().as_bytes()).unwrap();
-        write!(
-            self.out,
-            "\r{}",
-            termion::cursor::Right(self.line.pos as u16)
-        );
-        self.out.flush().unwrap();
+        self.refresh();
     }
 
     pub fn delete_chars(&mut self, bytes: &[u8]) {
@@ -391,15 +939,7 @@ impl Renderer {
             // delete backwards
             self.line.backspace(n.abs() as usize);
         }
-        // TODO: need to redraw more efficiently and with multiline
-        write!(self.out, "\r{}", termion::clear::CurrentLine);
-        self.out.write_all(self.line.as_str().as_bytes()).unwrap();
-        write!(
-            self.out,
-            "\r{}",
-            termion::cursor::Right(self.line.pos as u16)
-        );
-        self.out.flush().unwrap();
+        self.refresh();
     }
 }
 
@@ -469,9 +1009,13 @@ fn calculate_position(s: &str, orig: Position) -> Position {
 
 #[cfg(test)]
 mod test {
-    use super::{Direction, LineBuffer, MAX_LINE};
-    // use crate::keymap::{At, CharSearch, Word};
+    use super::{
+        calculate_position, CharSearch, Direction, LineBuffer, Position, Word, WordAction,
+        COLS, MAX_LINE,
+    };
+    // use crate::keymap::At;
     use std::cell::RefCell;
+    use std::io::{Read, Seek, SeekFrom, Write};
     use std::rc::Rc;
 
     #[test]
@@ -568,4 +1112,237 @@ mod test {
         assert_eq!(0, s.pos);
         assert_eq!(true, ok);
     }
+
+    #[test]
+    fn move_to_next_word_vi_vs_emacs() {
+        let mut s = LineBuffer::init("foo bar baz", 0);
+        assert_eq!(true, s.move_to_next_word(Word::Vi, 1));
+        assert_eq!(4, s.pos); // start of "bar"
+
+        let mut s = LineBuffer::init("foo bar baz", 0);
+        assert_eq!(true, s.move_to_next_word(Word::Emacs, 1));
+        assert_eq!(3, s.pos); // end of "foo"
+    }
+
+    #[test]
+    fn move_to_next_word_big_glues_punctuation_to_the_word() {
+        // Unlike `Vi`/`Emacs`, `Big` (vi's WORD) doesn't split on punctuation,
+        // so "foo,bar" is a single WORD and "baz" is the next one.
+        let mut s = LineBuffer::init("foo,bar baz", 0);
+        assert_eq!(true, s.move_to_next_word(Word::Big, 1));
+        assert_eq!(8, s.pos); // start of "baz", not "bar"
+
+        let mut s = LineBuffer::init("foo,bar baz", 0);
+        let chars = s.delete_word(Direction::Forward, Word::Big, 1);
+        assert_eq!(Some("foo,bar ".to_owned()), chars);
+        assert_eq!("baz", s.buf);
+    }
+
+    #[test]
+    fn move_to_prev_word() {
+        let mut s = LineBuffer::init("foo bar baz", 11);
+        assert_eq!(true, s.move_to_prev_word(Word::Emacs, 1));
+        assert_eq!(8, s.pos); // start of "baz"
+        assert_eq!(true, s.move_to_prev_word(Word::Emacs, 2));
+        assert_eq!(0, s.pos);
+    }
+
+    #[test]
+    fn delete_word() {
+        let mut s = LineBuffer::init("foo bar baz", 4);
+        let chars = s.delete_word(Direction::Forward, Word::Vi, 1);
+        assert_eq!(Some("bar ".to_owned()), chars);
+        assert_eq!("foo baz", s.buf);
+        assert_eq!(4, s.pos);
+
+        let mut s = LineBuffer::init("foo bar baz", 4);
+        let chars = s.delete_word(Direction::Backward, Word::Vi, 1);
+        assert_eq!(Some("foo ".to_owned()), chars);
+        assert_eq!("bar baz", s.buf);
+        assert_eq!(0, s.pos);
+    }
+
+    #[test]
+    fn edit_word() {
+        let mut s = LineBuffer::init("hello world", 0);
+        assert_eq!(true, s.edit_word(WordAction::Capitalize, Word::Emacs));
+        assert_eq!("Hello world", s.buf);
+        assert_eq!(5, s.pos);
+
+        let mut s = LineBuffer::init("hello world", 6);
+        assert_eq!(true, s.edit_word(WordAction::Uppercase, Word::Emacs));
+        assert_eq!("hello WORLD", s.buf);
+
+        let mut s = LineBuffer::init("HELLO world", 0);
+        assert_eq!(true, s.edit_word(WordAction::Lowercase, Word::Emacs));
+        assert_eq!("hello world", s.buf);
+    }
+
+    #[test]
+    fn kill_chain_forward_appends() {
+        let mut s = LineBuffer::init("abc def ghi", 0);
+        let killed = s.delete_word(Direction::Forward, Word::Vi, 1);
+        assert_eq!(Some("abc ".to_owned()), killed);
+        let killed = s.delete_word(Direction::Forward, Word::Vi, 1);
+        assert_eq!(Some("def ".to_owned()), killed);
+        assert_eq!("ghi", s.buf);
+
+        assert_eq!(true, s.yank(1));
+        assert_eq!("abc def ghi", s.buf);
+        assert_eq!(8, s.pos);
+    }
+
+    #[test]
+    fn kill_chain_backward_prepends() {
+        let mut s = LineBuffer::init("hello world", 11);
+        assert_eq!(true, s.backspace(5));
+        assert_eq!("hello ", s.buf);
+        assert_eq!(true, s.backspace(1));
+        assert_eq!("hello", s.buf);
+        assert_eq!(5, s.pos);
+
+        assert_eq!(true, s.yank(1));
+        assert_eq!("hello world", s.buf);
+        assert_eq!(11, s.pos);
+    }
+
+    #[test]
+    fn yank_pop_cycles_ring() {
+        let mut s = LineBuffer::init("aaa bbb", 7);
+        let killed = s.delete_word(Direction::Backward, Word::Vi, 1);
+        assert_eq!(Some("bbb".to_owned()), killed);
+        assert_eq!("aaa ", s.buf);
+
+        // Breaks the kill chain, so the next kill starts a fresh ring entry.
+        assert!(s.insert('!', 1).is_some());
+        assert_eq!(true, s.backspace(1));
+        assert_eq!("aaa ", s.buf);
+        assert_eq!(4, s.pos);
+
+        assert_eq!(true, s.yank(1));
+        assert_eq!("aaa !", s.buf);
+        assert_eq!(5, s.pos);
+
+        assert_eq!(true, s.yank_pop());
+        assert_eq!("aaa bbb", s.buf);
+        assert_eq!(7, s.pos);
+    }
+
+    #[test]
+    fn move_to_char_forward_and_backward() {
+        let mut s = LineBuffer::init("abcabc", 0);
+        assert_eq!(true, s.move_to_char(CharSearch::Forward('c'), 1));
+        assert_eq!(2, s.pos);
+        assert_eq!(true, s.move_to_char(CharSearch::Forward('c'), 1));
+        assert_eq!(5, s.pos);
+
+        assert_eq!(true, s.move_to_char(CharSearch::Backward('a'), 1));
+        assert_eq!(3, s.pos);
+
+        // Fewer than `n` matches exist: the cursor doesn't move.
+        assert_eq!(false, s.move_to_char(CharSearch::Forward('z'), 1));
+        assert_eq!(3, s.pos);
+    }
+
+    #[test]
+    fn move_to_char_till_variants() {
+        let mut s = LineBuffer::init("abcabc", 0);
+        assert_eq!(true, s.move_to_char(CharSearch::ForwardBefore('c'), 1));
+        assert_eq!(1, s.pos); // one grapheme before the first 'c'
+
+        let mut s = LineBuffer::init("abcabc", 5);
+        assert_eq!(true, s.move_to_char(CharSearch::BackwardAfter('a'), 1));
+        assert_eq!(4, s.pos); // one grapheme after the preceding 'a'
+    }
+
+    #[test]
+    fn repeat_last_char_search() {
+        let mut s = LineBuffer::init("abcabc", 0);
+        assert_eq!(true, s.move_to_char(CharSearch::Forward('c'), 1));
+        assert_eq!(2, s.pos);
+
+        assert_eq!(true, s.repeat_last_char_search(false, 1));
+        assert_eq!(5, s.pos);
+
+        // `,` reverses the last search: hunt backward for 'c' from here.
+        assert_eq!(true, s.repeat_last_char_search(true, 1));
+        assert_eq!(2, s.pos);
+    }
+
+    #[test]
+    fn calculate_position_embedded_newline() {
+        let pos = calculate_position("ab\ncd", Position::default());
+        assert_eq!(Position { col: 2, row: 1 }, pos);
+    }
+
+    #[test]
+    fn calculate_position_wraps_at_cols() {
+        let line: String = std::iter::repeat('x').take(COLS + 5).collect();
+        let pos = calculate_position(&line, Position::default());
+        assert_eq!(Position { col: 5, row: 1 }, pos);
+    }
+
+    #[test]
+    fn calculate_position_tab_stop() {
+        let pos = calculate_position("ab\t", Position::default());
+        assert_eq!(Position { col: 4, row: 0 }, pos);
+    }
+
+    #[test]
+    fn seek_start_and_current_are_grapheme_offsets() {
+        let mut s = LineBuffer::init("ö̲g̈", 0);
+        assert_eq!(4, s.seek(SeekFrom::Start(1)).unwrap());
+        assert_eq!(4, s.pos());
+
+        assert_eq!(7, s.seek(SeekFrom::Current(1)).unwrap());
+        assert_eq!(0, s.seek(SeekFrom::Current(-2)).unwrap());
+    }
+
+    #[test]
+    fn seek_end_counts_back_from_the_end() {
+        let mut s = LineBuffer::init("ö̲g̈", 0);
+        assert_eq!(7, s.seek(SeekFrom::End(0)).unwrap());
+        assert_eq!(4, s.seek(SeekFrom::End(-1)).unwrap());
+    }
+
+    #[test]
+    fn seek_clamps_out_of_range_offsets() {
+        let mut s = LineBuffer::init("ab", 0);
+        assert_eq!(2, s.seek(SeekFrom::Start(10)).unwrap());
+        assert_eq!(0, s.seek(SeekFrom::Current(-10)).unwrap());
+        assert_eq!(2, s.seek(SeekFrom::End(10)).unwrap());
+    }
+
+    #[test]
+    fn write_inserts_at_cursor_and_advances() {
+        let mut s = LineBuffer::with_capacity(MAX_LINE);
+        s.write_all(b"ab").unwrap();
+        s.seek(SeekFrom::Start(1)).unwrap();
+        s.write_all("α".as_bytes()).unwrap();
+        assert_eq!("aαb", s.buf);
+        assert_eq!(3, s.pos());
+    }
+
+    #[test]
+    fn write_rejects_invalid_utf8() {
+        let mut s = LineBuffer::with_capacity(MAX_LINE);
+        assert!(s.write_all(&[0xff, 0xfe]).is_err());
+        assert_eq!("", s.buf);
+    }
+
+    #[test]
+    fn read_snaps_down_to_a_char_boundary() {
+        let mut s = LineBuffer::init("aα", 0);
+        let mut out = [0u8; 2];
+        let n = s.read(&mut out).unwrap();
+        assert_eq!(1, n);
+        assert_eq!(b'a', out[0]);
+        assert_eq!(1, s.pos());
+
+        let mut out = [0u8; 8];
+        let n = s.read(&mut out).unwrap();
+        assert_eq!(2, n);
+        assert_eq!("α".as_bytes(), &out[..n]);
+        assert_eq!(3, s.pos());
+    }
 }